@@ -0,0 +1,91 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use arc_swap::ArcSwap;
+use tokio_rustls::rustls::internal::pemfile::{certs, rsa_private_keys};
+use tokio_rustls::rustls::sign::{CertifiedKey, RSASigningKey};
+use tokio_rustls::rustls::{ClientHello, ResolvesServerCert};
+
+use crate::Error;
+
+/// A `ResolvesServerCert` backed by an `ArcSwap`, so a long-running broker can rotate its
+/// certificate (e.g. short-lived ACME certs) without dropping existing sessions: new
+/// handshakes pick up whatever is currently swapped in, established connections are untouched.
+pub(crate) struct ReloadableCert {
+    current: ArcSwap<CertifiedKey>,
+}
+
+impl ReloadableCert {
+    pub(crate) fn new(cert_path: &str, key_path: &str) -> Result<ReloadableCert, Error> {
+        let key = load_certified_key(cert_path, key_path)?;
+        Ok(ReloadableCert {
+            current: ArcSwap::from_pointee(key),
+        })
+    }
+
+    /// Loads `cert_path`/`key_path` again and swaps the result in atomically. A malformed
+    /// cert/key is reported but never clears the currently-serving certificate.
+    pub(crate) fn reload(&self, cert_path: &str, key_path: &str) -> Result<(), Error> {
+        let key = load_certified_key(cert_path, key_path)?;
+        self.current.store(Arc::new(key));
+        Ok(())
+    }
+}
+
+impl ResolvesServerCert for ReloadableCert {
+    fn resolve(&self, _client_hello: ClientHello) -> Option<CertifiedKey> {
+        Some((**self.current.load()).clone())
+    }
+}
+
+fn load_certified_key(cert_path: &str, key_path: &str) -> Result<CertifiedKey, Error> {
+    let cert_file = File::open(cert_path)
+        .map_err(|_| Error::ServerCertNotFound(cert_path.to_string()))?;
+    let chain = certs(&mut BufReader::new(cert_file))
+        .map_err(|_| Error::InvalidServerCert(cert_path.to_string()))?;
+
+    let key_file =
+        File::open(key_path).map_err(|_| Error::ServerKeyNotFound(key_path.to_string()))?;
+    let keys = rsa_private_keys(&mut BufReader::new(key_file))
+        .map_err(|_| Error::InvalidServerKey(key_path.to_string()))?;
+    let key = keys
+        .first()
+        .ok_or_else(|| Error::InvalidServerKey(key_path.to_string()))?;
+
+    let signing_key = RSASigningKey::new(key)
+        .map_err(|_| Error::InvalidServerKey(key_path.to_string()))?;
+    Ok(CertifiedKey::new(chain, Arc::new(Box::new(signing_key))))
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).ok()?.modified().ok()
+}
+
+/// Polls `cert_path`/`key_path` mtimes on `poll_interval` and reloads `resolver` whenever
+/// either changes. Errors (malformed cert/key, file briefly missing mid-write) are logged and
+/// leave the previous certificate in place rather than panicking the watcher.
+pub(crate) async fn watch(
+    resolver: Arc<ReloadableCert>,
+    cert_path: String,
+    key_path: String,
+    poll_interval: Duration,
+) {
+    let mut last_seen = (modified(&cert_path), modified(&key_path));
+
+    loop {
+        tokio::time::sleep(poll_interval).await;
+
+        let seen = (modified(&cert_path), modified(&key_path));
+        if seen == last_seen {
+            continue;
+        }
+        last_seen = seen;
+
+        match resolver.reload(&cert_path, &key_path) {
+            Ok(()) => info!("Reloaded TLS certificate from {}", cert_path),
+            Err(e) => error!("Failed to reload TLS certificate {}: {:?}", cert_path, e),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use tokio_rustls::rustls::Certificate;
+use webpki::DnsNameRef;
+use x509_parser::prelude::*;
+
+use crate::CertAuthMode;
+
+/// Extracts the identity `cert_auth` should authenticate the connection against: the leaf
+/// certificate's subject CN, falling back to the first DNS SAN that is a valid DNS name when
+/// the CN is missing, multivalued or empty.
+pub(crate) fn identity_from_leaf(leaf: &Certificate) -> Option<String> {
+    let (_, cert) = X509Certificate::from_der(&leaf.0).ok()?;
+
+    // A single, non-empty CN is a usable identity. Zero CNs, more than one, or a blank one are
+    // all "no usable CN" - fall through to the SAN below rather than trusting an empty string
+    // or picking one of several CNs arbitrarily.
+    let cns: Vec<&str> = cert
+        .subject()
+        .iter_common_name()
+        .filter_map(|cn| cn.as_str().ok())
+        .collect();
+    let cn = match cns.as_slice() {
+        [single] if !single.is_empty() => Some(single.to_string()),
+        _ => None,
+    };
+
+    cn.or_else(|| {
+        cert.subject_alternative_name()
+            .ok()
+            .flatten()
+            .and_then(|san| {
+                san.value.general_names.iter().find_map(|name| match name {
+                    GeneralName::DNSName(dns) => DnsNameRef::try_from_ascii_str(dns)
+                        .ok()
+                        .map(|_| dns.to_string()),
+                    _ => None,
+                })
+            })
+    })
+}
+
+/// Checks `identity` (extracted from the client certificate) against the CONNECT packet's
+/// `client_id`/`username` per the configured [`CertAuthMode`].
+pub(crate) fn matches(mode: &CertAuthMode, identity: &str, client_id: &str, username: Option<&str>) -> bool {
+    match mode {
+        CertAuthMode::RequireCnMatchesClientId => identity == client_id,
+        CertAuthMode::MapCnToUsername => username.map(|u| u == identity).unwrap_or(false),
+    }
+}
@@ -14,6 +14,7 @@ use crate::remotelink::RemoteLink;
 
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::TcpListener;
+use tokio::sync::Semaphore;
 use tokio::{signal, task, time};
 
 // All requirements for `rustls`
@@ -21,7 +22,8 @@ use tokio::{signal, task, time};
 use tokio_rustls::rustls::internal::pemfile::{certs, rsa_private_keys};
 #[cfg(feature = "use-rustls")]
 use tokio_rustls::rustls::{
-    AllowAnyAuthenticatedClient, NoClientAuth, RootCertStore, ServerConfig, TLSError,
+    AllowAnyAuthenticatedClient, Certificate, NoClientAuth, PrivateKey, RootCertStore,
+    ServerConfig, TLSError,
 };
 #[cfg(feature = "use-rustls")]
 use tokio_rustls::TlsAcceptor;
@@ -35,15 +37,30 @@ use tokio_native_tls::native_tls::Error as TLSError;
 use tokio_native_tls::{native_tls, TlsAcceptor};
 
 pub mod async_locallink;
+#[cfg(feature = "use-rustls")]
+mod cert_reload;
+#[cfg(feature = "use-rustls")]
+mod certauth;
 mod consolelink;
 mod locallink;
+mod mesh;
 mod network;
+#[cfg(feature = "use-rustls")]
+mod outbound_tls;
+#[cfg(feature = "use-quic")]
+mod quic;
 mod remotelink;
 mod state;
+#[cfg(feature = "use-websocket")]
+mod websocket;
 
 use crate::consolelink::ConsoleLink;
 pub use crate::locallink::{LinkError, LinkRx, LinkTx};
 use crate::network::Network;
+#[cfg(feature = "use-quic")]
+use crate::quic::QuicIo;
+#[cfg(feature = "use-quic")]
+use futures_util::StreamExt;
 #[cfg(feature = "use-rustls")]
 use crate::Error::ServerKeyNotFound;
 use std::collections::HashMap;
@@ -82,6 +99,23 @@ pub enum Error {
     InvalidServerCert(String),
     #[error("Invalid server key file {0}")]
     InvalidServerKey(String),
+    #[error("Invalid server name {0}")]
+    InvalidServerName(String),
+    #[cfg(feature = "use-quic")]
+    #[error("QUIC endpoint error {0}")]
+    Quic(#[from] quinn::EndpointError),
+    #[cfg(feature = "use-quic")]
+    #[error("QUIC connection error {0}")]
+    QuicConnection(#[from] quinn::ConnectionError),
+    #[cfg(feature = "use-quic")]
+    #[error("Invalid QUIC certificate chain")]
+    InvalidQuicCert,
+    #[cfg(feature = "use-quic")]
+    #[error("Invalid QUIC private key")]
+    InvalidQuicKey,
+    #[cfg(feature = "use-quic")]
+    #[error("cert_auth is not supported on the QUIC listener")]
+    CertAuthUnsupportedOnQuic,
     Disconnected,
     NetworkClosed,
     WrongPacket(Packet),
@@ -111,6 +145,36 @@ pub struct ServerSettings {
     pub key_path: Option<String>,
     pub next_connection_delay_ms: u64,
     pub connections: ConnectionSettings,
+    /// Binds a QUIC endpoint on its own `QuicSettings::port` in addition to (not instead of) the
+    /// TCP/TLS listener, reusing the same certificate/key configured above.
+    #[serde(default)]
+    pub quic: Option<QuicSettings>,
+    /// When set, every accepted connection (TCP or TLS) is expected to perform a WebSocket
+    /// handshake requiring the `mqtt` subprotocol before MQTT framing begins.
+    #[serde(default)]
+    pub websocket: bool,
+    /// ALPN protocols the TLS (and QUIC) acceptors advertise. When set, a TLS connection that
+    /// negotiates none of them is dropped. When left unset, `["mqtt"]` is advertised but not
+    /// enforced.
+    #[serde(default)]
+    pub alpn: Option<Vec<String>>,
+    /// Caps the number of connection handshakes (TLS accept and/or WebSocket upgrade) that may
+    /// be in flight (accepted but not yet established) at once, so a burst of stalled/malicious
+    /// handshakes can't monopolize the runtime while already-established sessions keep running.
+    /// The QUIC listener gets a separate cap of the same size. A value of `0` rejects every
+    /// connection (`Semaphore::new(0)` never hands out a permit), so treat `0` as "closed", not
+    /// "unbounded".
+    #[serde(default = "default_max_pending_connections")]
+    pub max_pending_connections: usize,
+}
+
+fn default_max_pending_connections() -> usize {
+    100
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuicSettings {
+    pub port: u16,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -123,12 +187,43 @@ pub struct ConnectionSettings {
     pub max_inflight_size: usize,
     pub username: Option<String>,
     pub password: Option<String>,
+    /// When set, a successful mutual-TLS handshake is required and the client certificate's
+    /// identity (subject CN, falling back to the first DNS SAN) is checked against the
+    /// CONNECT packet per the chosen mode. Enforced only in `use-rustls` builds; other builds
+    /// ignore it.
+    #[serde(default)]
+    pub cert_auth: Option<CertAuthMode>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum CertAuthMode {
+    /// The MQTT `client_id` must equal the certificate identity.
+    RequireCnMatchesClientId,
+    /// The MQTT `username` must equal the certificate identity.
+    MapCnToUsername,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MeshSettings {
     pub host: String,
     pub port: u16,
+    /// Encrypts this mesh link instead of dialing plaintext. See [`outbound_tls::connect`].
+    /// The `replicator` link doesn't have an equivalent yet - it has no host/port of its own
+    /// to dial, so there's nothing for a `tls` field there to configure.
+    #[serde(default)]
+    pub tls: Option<MeshTlsSettings>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeshTlsSettings {
+    /// Trusted CA for verifying the peer; falls back to `webpki-roots` when unset.
+    pub ca_path: Option<String>,
+    /// Client certificate, for mutual TLS. Requires `key_path`.
+    pub cert_path: Option<String>,
+    /// Client private key, for mutual TLS. Requires `cert_path`.
+    pub key_path: Option<String>,
+    /// Expected server name, used for both SNI and certificate verification.
+    pub server_name: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -158,6 +253,8 @@ pub struct Broker {
     config: Arc<Config>,
     router_tx: Sender<(Id, Event)>,
     router: Option<Router>,
+    #[cfg(feature = "use-rustls")]
+    cert_resolvers: Vec<(String, String, Arc<cert_reload::ReloadableCert>)>,
 }
 
 impl Broker {
@@ -169,6 +266,22 @@ impl Broker {
             config,
             router_tx,
             router: Some(router),
+            #[cfg(feature = "use-rustls")]
+            cert_resolvers: Vec::new(),
+        }
+    }
+
+    /// Reloads every server's TLS certificate from disk, atomically swapping it in for new
+    /// handshakes. Established connections are unaffected. A malformed cert/key is logged and
+    /// leaves the previous, still-valid certificate in place. A QUIC endpoint keeps the
+    /// certificate it was bound with.
+    #[cfg(feature = "use-rustls")]
+    pub fn reload_certs(&self) {
+        for (cert_path, key_path, resolver) in &self.cert_resolvers {
+            match resolver.reload(cert_path, key_path) {
+                Ok(()) => info!("Reloaded TLS certificate from {}", cert_path),
+                Err(e) => error!("Failed to reload TLS certificate {}: {:?}", cert_path, e),
+            }
         }
     }
 
@@ -194,7 +307,25 @@ impl Broker {
         for (id, config) in self.config.servers.clone() {
             let server_name = format!("rumqttd-server-{}", id);
             let server_thread = thread::Builder::new().name(server_name);
+
+            #[cfg(feature = "use-rustls")]
+            let cert_resolver = match (config.cert_path.clone(), config.key_path.clone()) {
+                (Some(cert_path), Some(key_path)) => {
+                    let resolver = Arc::new(cert_reload::ReloadableCert::new(
+                        &cert_path, &key_path,
+                    )?);
+                    self.cert_resolvers
+                        .push((cert_path, key_path, resolver.clone()));
+                    Some(resolver)
+                }
+                _ => None,
+            };
+
+            #[cfg(feature = "use-rustls")]
+            let server = Server::new(id, config, self.router_tx.clone(), cert_resolver);
+            #[cfg(not(feature = "use-rustls"))]
             let server = Server::new(id, config, self.router_tx.clone());
+
             server_thread.spawn(move || {
                 let mut runtime = tokio::runtime::Builder::new_current_thread();
                 let runtime = runtime.enable_all().build().unwrap();
@@ -209,6 +340,41 @@ impl Broker {
         let mut runtime = tokio::runtime::Builder::new_current_thread();
         let runtime = runtime.enable_all().build().unwrap();
 
+        // Dial every configured mesh peer, wrapping the outgoing TcpStream with TLS when the
+        // peer has `tls` set. The replication protocol carried over the resulting connection
+        // lives outside this module.
+        if let Some(cluster) = self.config.cluster.clone() {
+            let max_incoming_size = self
+                .config
+                .replicator
+                .as_ref()
+                .map(|c| c.max_payload_size)
+                .unwrap_or(1024 * 1024);
+
+            for (peer_id, mesh) in cluster {
+                runtime.spawn(async move {
+                    let mut network = match mesh::dial(&mesh, max_incoming_size).await {
+                        Ok(network) => network,
+                        Err(e) => {
+                            error!("Failed to connect to mesh peer {}: {:?}", peer_id, e);
+                            return;
+                        }
+                    };
+                    info!("Connected to mesh peer {}", peer_id);
+
+                    // The mesh replication protocol carried over this connection lives
+                    // outside this module; keep the link open (rather than dropping it right
+                    // after dialing) and log when the peer actually goes away.
+                    loop {
+                        if let Err(e) = network.read().await {
+                            info!("Mesh peer {} disconnected: {:?}", peer_id, e);
+                            break;
+                        }
+                    }
+                });
+            }
+        }
+
         // Run console in current thread, if it is configured.
         if self.config.console.is_some() {
             let console = ConsoleLink::new(self.config.clone(), self.router_tx.clone());
@@ -218,6 +384,19 @@ impl Broker {
             });
         }
 
+        #[cfg(all(feature = "use-rustls", unix))]
+        runtime.block_on(async {
+            let mut sighup =
+                signal::unix::signal(signal::unix::SignalKind::hangup()).unwrap();
+            loop {
+                tokio::select! {
+                    _ = signal::ctrl_c() => break,
+                    _ = sighup.recv() => self.reload_certs(),
+                }
+            }
+        });
+
+        #[cfg(not(all(feature = "use-rustls", unix)))]
         runtime.block_on(async {
             signal::ctrl_c().await.unwrap();
         });
@@ -226,13 +405,32 @@ impl Broker {
     }
 }
 
+#[cfg_attr(feature = "use-quic", derive(Clone))]
 struct Server {
     id: String,
     config: ServerSettings,
     router_tx: Sender<(Id, Event)>,
+    #[cfg(feature = "use-rustls")]
+    cert_resolver: Option<Arc<cert_reload::ReloadableCert>>,
 }
 
 impl Server {
+    #[cfg(feature = "use-rustls")]
+    pub fn new(
+        id: String,
+        config: ServerSettings,
+        router_tx: Sender<(Id, Event)>,
+        cert_resolver: Option<Arc<cert_reload::ReloadableCert>>,
+    ) -> Server {
+        Server {
+            id,
+            config,
+            router_tx,
+            cert_resolver,
+        }
+    }
+
+    #[cfg(not(feature = "use-rustls"))]
     pub fn new(id: String, config: ServerSettings, router_tx: Sender<(Id, Event)>) -> Server {
         Server {
             id,
@@ -274,9 +472,11 @@ impl Server {
         }
     }
 
+    /// Loads the certificate chain and private key pointed to by `cert_path`/`key_path`, shared
+    /// between the TLS and QUIC acceptors so both speak for the same identity.
     #[cfg(feature = "use-rustls")]
-    fn tls(&self) -> Result<Option<Arc<TlsAcceptor>>, Error> {
-        let (certs, key) = match self.config.cert_path.clone() {
+    fn certs_and_key(&self) -> Result<Option<(Vec<Certificate>, PrivateKey)>, Error> {
+        match self.config.cert_path.clone() {
             Some(cert) => {
                 // Get certificates
                 let cert_file = File::open(&cert);
@@ -298,10 +498,17 @@ impl Server {
                     None => return Err(Error::InvalidServerKey(key.clone())),
                 };
 
-                (certs, key)
+                Ok(Some((certs, key)))
             }
-            None => return Ok(None),
-        };
+            None => Ok(None),
+        }
+    }
+
+    #[cfg(feature = "use-rustls")]
+    fn tls(&self) -> Result<Option<Arc<TlsAcceptor>>, Error> {
+        if self.config.cert_path.is_none() {
+            return Ok(None);
+        }
 
         // client authentication with a CA. CA isn't required otherwise
         let mut server_config = match self.config.ca_path.clone() {
@@ -317,11 +524,115 @@ impl Server {
             None => ServerConfig::new(NoClientAuth::new()),
         };
 
-        server_config.set_single_cert(certs, key)?;
+        // The resolver (built once in `Broker::start` and kept warm by the reload watcher) is
+        // consulted on every handshake, so a cert rotation needs no restart here.
+        match &self.cert_resolver {
+            Some(resolver) => server_config.cert_resolver = resolver.clone(),
+            None => {
+                let (certs, key) = self.certs_and_key()?.ok_or(Error::ServerCertRequired)?;
+                server_config.set_single_cert(certs, key)?;
+            }
+        }
+
+        // Default to `mqtt` so the broker can sit behind an ALPN-routing load balancer
+        // alongside other protocols without extra configuration.
+        let alpn = self
+            .config
+            .alpn
+            .clone()
+            .unwrap_or_else(|| vec!["mqtt".to_string()]);
+        server_config.set_protocols(
+            &alpn
+                .iter()
+                .map(|p| p.as_bytes().to_vec())
+                .collect::<Vec<_>>(),
+        );
+
         let acceptor = TlsAcceptor::from(Arc::new(server_config));
         Ok(Some(Arc::new(acceptor)))
     }
 
+
+    #[cfg(feature = "use-quic")]
+    async fn start_quic(&self, quic: &QuicSettings) -> Result<(), Error> {
+        let (certs, key) = self.certs_and_key()?.ok_or(Error::ServerCertRequired)?;
+        let (_endpoint, mut incoming) = quic::build_endpoint(&self.config, quic, certs, key)?;
+
+        let config = Arc::new(self.config.connections.clone());
+        let max_incoming_size = config.max_payload_size;
+        let mut count: u32 = 0;
+
+        let handshake_timeout =
+            Duration::from_millis(self.config.connections.connection_timeout_ms as u64);
+        let pending_handshakes = Arc::new(Semaphore::new(self.config.max_pending_connections));
+
+        info!(
+            "Waiting for QUIC connections on port {}. Server = {}",
+            quic.port, self.id
+        );
+
+        while let Some(connecting) = incoming.next().await {
+            let router_tx = self.router_tx.clone();
+            let config = config.clone();
+
+            // Same bound as the TCP path: cap in-flight (not-yet-established) QUIC handshakes
+            // so a client opening connections without ever sending CONNECT can't exhaust
+            // resources, while established sessions keep running.
+            let pending_handshakes = pending_handshakes.clone();
+            let permit = match pending_handshakes.try_acquire_owned() {
+                Ok(permit) => permit,
+                Err(_) => {
+                    error!(
+                        "{}. Too many in-flight QUIC handshakes, dropping connection",
+                        count
+                    );
+                    count += 1;
+                    continue;
+                }
+            };
+
+            task::spawn(async move {
+                // Bound the QUIC handshake and the wait for the peer's one bidi stream with a
+                // single deadline, mirroring the TCP path's handshake_timeout, so a connection
+                // that never opens a stream can't pin this task indefinitely.
+                let stream = time::timeout(handshake_timeout, async {
+                    let connection = connecting.await?;
+                    let mut bi_streams = connection.bi_streams;
+                    match bi_streams.next().await {
+                        Some(Ok(stream)) => Ok(stream),
+                        Some(Err(e)) => Err(Error::QuicConnection(e)),
+                        None => Err(Error::NetworkClosed),
+                    }
+                })
+                .await;
+
+                let (send, recv) = match stream {
+                    Ok(Ok(stream)) => stream,
+                    Ok(Err(e)) => {
+                        error!("{}. Unable to establish QUIC connection. {:?}", count, e);
+                        return;
+                    }
+                    Err(_) => {
+                        error!("{}. QUIC handshake/stream open timed out", count);
+                        return;
+                    }
+                };
+
+                drop(permit);
+
+                let network = Network::new(QuicIo::new(send, recv), max_incoming_size);
+                let connector = Connector::new(config, router_tx, None);
+                if let Err(e) = connector.new_connection(network).await {
+                    error!("Dropping QUIC link task!! Result = {:?}", e);
+                }
+            });
+
+            count += 1;
+        }
+
+        Ok(())
+    }
+
     async fn start(&self) -> Result<(), Error> {
         let addr = format!("0.0.0.0:{}", self.config.port);
 
@@ -332,6 +643,41 @@ impl Server {
         let config = Arc::new(self.config.connections.clone());
         let max_incoming_size = config.max_payload_size;
         let acceptor = self.tls()?;
+        let handshake_timeout =
+            Duration::from_millis(self.config.connections.connection_timeout_ms as u64);
+        let pending_handshakes = Arc::new(Semaphore::new(self.config.max_pending_connections));
+
+        #[cfg(feature = "use-rustls")]
+        if let (Some(resolver), Some(cert_path), Some(key_path)) = (
+            self.cert_resolver.clone(),
+            self.config.cert_path.clone(),
+            self.config.key_path.clone(),
+        ) {
+            task::spawn(cert_reload::watch(
+                resolver,
+                cert_path,
+                key_path,
+                Duration::from_secs(30),
+            ));
+        }
+
+        #[cfg(feature = "use-quic")]
+        if let Some(quic) = self.config.quic.clone() {
+            // The QUIC listener never extracts a peer certificate identity (build_endpoint
+            // doesn't request one and start_quic always passes `cert_identity: None`), so
+            // cert_auth would silently reject every QUIC CONNECT rather than enforcing the
+            // configured policy. Refuse to start instead of breaking it quietly.
+            if self.config.connections.cert_auth.is_some() {
+                return Err(Error::CertAuthUnsupportedOnQuic);
+            }
+
+            let this = self.clone();
+            task::spawn(async move {
+                if let Err(e) = this.start_quic(&quic).await {
+                    error!("QUIC accept loop error: {:?}", e);
+                }
+            });
+        }
 
         info!("Waiting for connections on {}. Server = {}", addr, self.id);
         loop {
@@ -347,34 +693,125 @@ impl Server {
             // Cloneconfig
             let config = config.clone();
 
+            let websocket = self.config.websocket;
+            let alpn = self.config.alpn.clone();
+            // A single deadline budgets the whole handshake - TLS accept plus, when enabled,
+            // the WebSocket upgrade - instead of giving each step its own full
+            // connection_timeout_ms and letting a slow peer add them up.
+            let deadline = time::Instant::now() + handshake_timeout;
+
+            // Acquire a permit for the handshake before spawning so a burst of slow/malicious
+            // peers can't pile up unbounded tasks; the permit is dropped (freeing a slot) as
+            // soon as the handshake resolves, one way or another.
+            let pending_handshakes = pending_handshakes.clone();
+            let permit = match pending_handshakes.try_acquire_owned() {
+                Ok(permit) => permit,
+                Err(_) => {
+                    error!(
+                        "{}. Too many in-flight handshakes, dropping connection from {}",
+                        count, addr
+                    );
+                    count += 1;
+                    time::sleep(delay).await;
+                    continue;
+                }
+            };
+
             // Then spawn a new thread to handle the connection
             task::spawn(async move {
-                let network = match acceptor {
+                let (network, cert_identity) = match acceptor {
                     Some(acceptor) => {
                         info!("{}. Accepting TLS connection from: {}", count, addr);
 
-                        // Handle acceptor error
-                        let sock = match acceptor.accept(stream).await {
-                            Ok(s) => s,
-                            Err(e) => {
+                        // Handle acceptor error, bounding how long a stalled handshake can hold
+                        // its permit.
+                        let sock = match time::timeout_at(deadline, acceptor.accept(stream)).await {
+                            Ok(Ok(s)) => s,
+                            Ok(Err(e)) => {
                                 error!(
                                     "{}. Unable to acccept TLS connection. Result = {:?}",
                                     count, e
                                 );
                                 return;
                             }
+                            Err(_) => {
+                                error!("{}. TLS handshake timed out from {}", count, addr);
+                                return;
+                            }
+                        };
+
+                        #[cfg(feature = "use-rustls")]
+                        if let Err(e) = check_alpn(&alpn, &sock) {
+                            error!("{}. ALPN mismatch, dropping connection. {:?}", count, e);
+                            return;
+                        }
+
+                        // Pull the client certificate identity out now, while the TLS session
+                        // is still reachable, so it can ride along to `Connector::new_connection`.
+                        #[cfg(feature = "use-rustls")]
+                        let cert_identity = {
+                            let (_, session) = sock.get_ref();
+                            session
+                                .get_peer_certificates()
+                                .and_then(|certs| certs.first().cloned())
+                                .and_then(|leaf| certauth::identity_from_leaf(&leaf))
+                        };
+                        #[cfg(not(feature = "use-rustls"))]
+                        let cert_identity: Option<String> = None;
+
+                        let network = if websocket {
+                            #[cfg(feature = "use-websocket")]
+                            match time::timeout_at(deadline, websocket::accept(sock, count)).await {
+                                Ok(Ok(ws)) => Network::new(ws, max_incoming_size),
+                                _ => {
+                                    error!("{}. WebSocket handshake failed or timed out", count);
+                                    return;
+                                }
+                            }
+                            #[cfg(not(feature = "use-websocket"))]
+                            {
+                                error!("{}. WebSocket support not compiled in", count);
+                                return;
+                            }
+                        } else {
+                            Network::new(sock, max_incoming_size)
                         };
-                        Network::new(sock, max_incoming_size)
+
+                        (network, cert_identity)
                     }
                     None => {
                         info!("{}. Accepting TCP connection from: {}", count, addr);
-                        Network::new(stream, max_incoming_size)
+
+                        let network = if websocket {
+                            #[cfg(feature = "use-websocket")]
+                            match time::timeout_at(deadline, websocket::accept(stream, count)).await {
+                                Ok(Ok(ws)) => Network::new(ws, max_incoming_size),
+                                _ => {
+                                    error!("{}. WebSocket handshake failed or timed out", count);
+                                    return;
+                                }
+                            }
+                            #[cfg(not(feature = "use-websocket"))]
+                            {
+                                error!("{}. WebSocket support not compiled in", count);
+                                return;
+                            }
+                        } else {
+                            Network::new(stream, max_incoming_size)
+                        };
+
+                        (network, None)
                     }
                 };
 
+                // The handshake is done (or the branch above has already returned): free the
+                // slot for the next in-flight handshake before handing off to the long-running
+                // session, so established connections never hold a permit.
+                drop(permit);
+
                 let config = config.clone();
 
-                let connector = Connector::new(config, router_tx);
+                let connector = Connector::new(config, router_tx, cert_identity);
                 if let Err(e) = connector.new_connection(network).await {
                     error!("Dropping link task!! Result = {:?}", e);
                 }
@@ -383,20 +820,56 @@ impl Server {
             // Increment count
             count += 1;
 
-            // Wait a certain amount between connection attempts.
+            // Pace accepts per `next_connection_delay_ms`. The handshake itself runs in the
+            // task spawned above, so this doesn't serialize behind it - it only throttles how
+            // fast we call `accept()` again.
             time::sleep(delay).await;
         }
     }
 }
 
+/// Rejects a just-accepted TLS stream that didn't negotiate one of the configured ALPN
+/// protocols. `rustls` doesn't fail the handshake itself on a mismatch, so this must be
+/// checked explicitly right after `accept()` and before any MQTT bytes are read.
+#[cfg(feature = "use-rustls")]
+fn check_alpn<IO>(
+    alpn: &Option<Vec<String>>,
+    stream: &tokio_rustls::server::TlsStream<IO>,
+) -> Result<(), Error> {
+    let alpn = match alpn {
+        Some(alpn) => alpn,
+        None => return Ok(()),
+    };
+
+    let (_, session) = stream.get_ref();
+    match session.get_alpn_protocol() {
+        Some(negotiated) if alpn.iter().any(|p| p.as_bytes() == negotiated) => Ok(()),
+        negotiated => Err(Error::Io(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            format!("ALPN mismatch, negotiated {:?}, expected one of {:?}", negotiated, alpn),
+        ))),
+    }
+}
+
 struct Connector {
     config: Arc<ConnectionSettings>,
     router_tx: Sender<(Id, Event)>,
+    /// Identity extracted from the peer's TLS client certificate, when one was presented.
+    /// Consumed by `RemoteLink::new` to enforce `ConnectionSettings::cert_auth`.
+    cert_identity: Option<String>,
 }
 
 impl Connector {
-    fn new(config: Arc<ConnectionSettings>, router_tx: Sender<(Id, Event)>) -> Connector {
-        Connector { config, router_tx }
+    fn new(
+        config: Arc<ConnectionSettings>,
+        router_tx: Sender<(Id, Event)>,
+        cert_identity: Option<String>,
+    ) -> Connector {
+        Connector {
+            config,
+            router_tx,
+            cert_identity,
+        }
     }
 
     /// A new network connection should wait for mqtt connect packet. This handling should be handled
@@ -407,9 +880,13 @@ impl Connector {
     async fn new_connection(&self, network: Network) -> Result<(), Error> {
         let config = self.config.clone();
         let router_tx = self.router_tx.clone();
+        let cert_identity = self.cert_identity.clone();
 
-        // Start the link
-        let (client_id, id, mut link) = RemoteLink::new(config, router_tx, network).await?;
+        // Start the link. When `cert_auth` is configured, `RemoteLink::new` rejects a CONNECT
+        // whose `client_id`/`username` doesn't match `cert_identity` (and any CONNECT at all
+        // when no client certificate was presented).
+        let (client_id, id, mut link) =
+            RemoteLink::new(config, router_tx, network, cert_identity).await?;
         let (execute_will, pending) = match link.start().await {
             // Connection get close. This shouldn't usually happen
             Ok(_) => {
@@ -0,0 +1,20 @@
+use tokio::net::TcpStream;
+
+use crate::network::Network;
+use crate::{Error, MeshSettings};
+
+/// Dials `mesh.host:mesh.port` and, when `mesh.tls` is configured, wraps the resulting
+/// `TcpStream` with the outbound TLS connector ([`crate::outbound_tls::connect`]) before handing
+/// it to the rest of the connection machinery - mirrors the server-side cert handling already in
+/// `Server::tls()`.
+pub(crate) async fn dial(mesh: &MeshSettings, max_incoming_size: usize) -> Result<Network, Error> {
+    let stream = TcpStream::connect((mesh.host.as_str(), mesh.port)).await?;
+
+    #[cfg(feature = "use-rustls")]
+    if let Some(tls) = &mesh.tls {
+        let stream = crate::outbound_tls::connect(stream, tls).await?;
+        return Ok(Network::new(stream, max_incoming_size));
+    }
+
+    Ok(Network::new(stream, max_incoming_size))
+}
@@ -0,0 +1,65 @@
+use std::io::BufReader;
+use std::fs::File;
+use std::sync::Arc;
+
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::internal::pemfile::{certs, rsa_private_keys};
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::webpki::DNSNameRef;
+use tokio_rustls::{client::TlsStream, TlsConnector};
+
+use crate::{Error, MeshTlsSettings};
+
+/// Builds a `TlsConnector` for an outbound mesh link: trusts `ca_path` when given,
+/// otherwise falls back to the bundled `webpki-roots`, and presents a client certificate
+/// (mutual TLS) when `cert_path`/`key_path` are both set. Mirrors the server-side cert handling
+/// already in `Server::tls()`.
+pub(crate) fn connector(tls: &MeshTlsSettings) -> Result<TlsConnector, Error> {
+    let mut roots = RootCertStore::empty();
+    match &tls.ca_path {
+        Some(ca) => {
+            let ca_file = File::open(ca).map_err(|_| Error::CaFileNotFound(ca.clone()))?;
+            roots
+                .add_pem_file(&mut BufReader::new(ca_file))
+                .map_err(|_| Error::InvalidCACert(ca.clone()))?;
+        }
+        None => {
+            roots.add_server_trust_anchors(&webpki_roots::TLS_SERVER_ROOTS);
+        }
+    }
+
+    let mut config = ClientConfig::new();
+    config.root_store = roots;
+
+    if let (Some(cert_path), Some(key_path)) = (&tls.cert_path, &tls.key_path) {
+        let cert_file =
+            File::open(cert_path).map_err(|_| Error::ServerCertNotFound(cert_path.clone()))?;
+        let chain = certs(&mut BufReader::new(cert_file))
+            .map_err(|_| Error::InvalidServerCert(cert_path.clone()))?;
+
+        let key_file =
+            File::open(key_path).map_err(|_| Error::ServerKeyNotFound(key_path.clone()))?;
+        let keys = rsa_private_keys(&mut BufReader::new(key_file))
+            .map_err(|_| Error::InvalidServerKey(key_path.clone()))?;
+        let key = keys
+            .first()
+            .ok_or_else(|| Error::InvalidServerKey(key_path.clone()))?;
+
+        config.set_single_client_cert(chain, key.clone())?;
+    }
+
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
+/// Connects `stream` to `tls.server_name` over TLS, using a connector freshly built by
+/// [`connector`] on each call. Called by the mesh link in place of handing the bare `TcpStream`
+/// straight to the existing connection machinery.
+pub(crate) async fn connect(
+    stream: TcpStream,
+    tls: &MeshTlsSettings,
+) -> Result<TlsStream<TcpStream>, Error> {
+    let connector = connector(tls)?;
+    let server_name = DNSNameRef::try_from_ascii_str(&tls.server_name)
+        .map_err(|_| Error::InvalidServerName(tls.server_name.clone()))?;
+    Ok(connector.connect(server_name, stream).await?)
+}
@@ -0,0 +1,95 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use quinn::{Endpoint, RecvStream, SendStream};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_rustls::rustls::{Certificate, PrivateKey};
+
+use crate::{Error, QuicSettings, ServerSettings};
+
+/// Binds a QUIC endpoint on `quic.port` using the certificate chain and key that
+/// `Server::certs_and_key()` loaded, advertising the `mqtt` ALPN (or `config.alpn` when set).
+///
+/// `certs`/`key` are the same `rustls` types `Server::certs_and_key()` loaded; quinn has its own
+/// (DER-identical) certificate/key types, so they're re-wrapped from the raw DER here rather
+/// than making the caller deal with two certificate representations.
+pub(crate) fn build_endpoint(
+    config: &ServerSettings,
+    quic: &QuicSettings,
+    certs: Vec<Certificate>,
+    key: PrivateKey,
+) -> Result<(Endpoint, quinn::Incoming), Error> {
+    let certs = certs
+        .iter()
+        .map(|c| quinn::Certificate::from_der(&c.0))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| Error::InvalidQuicCert)?;
+    let key = quinn::PrivateKey::from_der(&key.0).map_err(|_| Error::InvalidQuicKey)?;
+
+    let cert_chain = quinn::CertificateChain::from_certs(certs);
+    let alpn = config
+        .alpn
+        .clone()
+        .unwrap_or_else(|| vec!["mqtt".to_string()]);
+
+    let mut transport = quinn::ServerConfigBuilder::default();
+    transport.certificate(cert_chain, key)?;
+    let protocols: Vec<Vec<u8>> = alpn.iter().map(|p| p.as_bytes().to_vec()).collect();
+    transport.protocols(
+        protocols
+            .iter()
+            .map(|p| p.as_slice())
+            .collect::<Vec<_>>()
+            .as_slice(),
+    );
+
+    let mut endpoint = quinn::Endpoint::builder();
+    endpoint.listen(transport.build());
+
+    let addr = format!("0.0.0.0:{}", quic.port).parse().unwrap();
+    Ok(endpoint.bind(&addr)?)
+}
+
+/// Adapts a single QUIC bidirectional stream to [`crate::IO`] so the MQTT byte framing
+/// can flow through the same [`crate::network::Network`] path used for TCP/TLS.
+///
+/// One MQTT session maps to exactly one bidi stream per QUIC connection (v4 compatibility) -
+/// the accept loop uses the first bidi stream the peer opens and never accepts further ones.
+pub(crate) struct QuicIo {
+    send: SendStream,
+    recv: RecvStream,
+}
+
+impl QuicIo {
+    pub(crate) fn new(send: SendStream, recv: RecvStream) -> QuicIo {
+        QuicIo { send, recv }
+    }
+}
+
+impl AsyncRead for QuicIo {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.recv).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for QuicIo {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.send).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.send).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.send).poll_shutdown(cx)
+    }
+}
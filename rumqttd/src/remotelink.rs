@@ -0,0 +1,108 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use mqttbytes::v4::Packet;
+use rumqttlog::*;
+use tokio::time;
+
+use crate::network::Network;
+use crate::state::State;
+use crate::{ConnectionSettings, Id};
+
+#[cfg(feature = "use-rustls")]
+use crate::certauth;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("I/O {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Mqtt error {0}")]
+    Mqtt(#[from] mqttbytes::Error),
+    #[error("Channel send error")]
+    Send(#[from] SendError<(Id, Event)>),
+    #[error("Channel recv error")]
+    Recv(#[from] RecvError),
+    #[error("Expected connect packet, got {0:?}")]
+    NotConnectPacket(Packet),
+    #[error("Client disconnected")]
+    Disconnect,
+    #[error("cert_auth is configured but the client presented no certificate with a usable identity")]
+    CertAuthMissing,
+    #[error("certificate identity does not match the CONNECT client_id/username")]
+    CertAuthMismatch,
+}
+
+pub(crate) struct RemoteLink {
+    client_id: String,
+    id: Id,
+    network: Network,
+    router_tx: Sender<(Id, Event)>,
+    pub(crate) state: State,
+}
+
+impl RemoteLink {
+    /// Waits for the MQTT CONNECT packet and, when `config.cert_auth` is set, rejects it unless
+    /// `cert_identity` (pulled out of the peer's TLS client certificate in `Server::start`)
+    /// matches the CONNECT's `client_id`/`username` per the configured [`crate::CertAuthMode`].
+    /// A CONNECT arriving with no client certificate at all is rejected the same way.
+    pub(crate) async fn new(
+        config: Arc<ConnectionSettings>,
+        router_tx: Sender<(Id, Event)>,
+        mut network: Network,
+        cert_identity: Option<String>,
+    ) -> Result<(String, Id, RemoteLink), Error> {
+        // Bound the wait for CONNECT so a client that connects but never sends one can't hold
+        // this task (and its connection) indefinitely.
+        let timeout = Duration::from_millis(config.connection_timeout_ms as u64);
+        let packet = match time::timeout(timeout, network.read()).await {
+            Ok(packet) => packet?,
+            Err(_) => {
+                return Err(Error::Io(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "timed out waiting for CONNECT",
+                )))
+            }
+        };
+        let connect = match packet {
+            Packet::Connect(connect) => connect,
+            packet => return Err(Error::NotConnectPacket(packet)),
+        };
+
+        #[cfg(feature = "use-rustls")]
+        if let Some(mode) = &config.cert_auth {
+            let identity = cert_identity.as_deref().ok_or(Error::CertAuthMissing)?;
+            let username = connect.login.as_ref().map(|login| login.username.as_str());
+            if !certauth::matches(mode, identity, &connect.client_id, username) {
+                error!(
+                    "Rejecting CONNECT from '{}': certificate identity doesn't match",
+                    connect.client_id
+                );
+                return Err(Error::CertAuthMismatch);
+            }
+        }
+
+        let client_id = connect.client_id.clone();
+        let (id, state) = State::register(&router_tx, &config, &connect).await?;
+
+        Ok((
+            client_id,
+            id,
+            RemoteLink {
+                client_id: connect.client_id,
+                id,
+                network,
+                router_tx,
+                state,
+            },
+        ))
+    }
+
+    pub(crate) async fn start(&mut self) -> Result<(), Error> {
+        loop {
+            match self.network.read().await? {
+                Packet::Disconnect => return Err(Error::Disconnect),
+                packet => self.state.handle_incoming(self.id, &self.router_tx, packet).await?,
+            }
+        }
+    }
+}
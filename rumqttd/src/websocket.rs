@@ -0,0 +1,141 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::{ready, SinkExt, StreamExt};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+const SUBPROTOCOL: &str = "mqtt";
+
+/// Performs the HTTP-upgrade handshake on `stream`, requiring the `mqtt` subprotocol, and
+/// returns a [`WsIo`] ready to carry binary MQTT frames.
+pub(crate) async fn accept<S>(stream: S, count: u32) -> Result<WsIo<S>, crate::Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let callback = |req: &Request, mut response: Response| {
+        let offers_mqtt = req
+            .headers()
+            .get_all("Sec-WebSocket-Protocol")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .any(|v| v.split(',').any(|p| p.trim() == SUBPROTOCOL));
+
+        if !offers_mqtt {
+            error!(
+                "{}. WebSocket client did not offer the '{}' subprotocol",
+                count, SUBPROTOCOL
+            );
+            return Err(tokio_tungstenite::tungstenite::http::Response::builder()
+                .status(400)
+                .body(None)
+                .unwrap());
+        }
+
+        response.headers_mut().insert(
+            "Sec-WebSocket-Protocol",
+            SUBPROTOCOL.parse().unwrap(),
+        );
+        Ok(response)
+    };
+
+    let ws = tokio_tungstenite::accept_hdr_async(stream, callback)
+        .await
+        .map_err(|_| crate::Error::Disconnected)?;
+    Ok(WsIo::new(ws))
+}
+
+/// Adapts a `WebSocketStream` carrying binary MQTT frames to [`crate::IO`] so the existing
+/// [`crate::network::Network`] can read/write a continuous MQTT byte stream, buffering any
+/// bytes left over from a partially-consumed binary message across calls.
+pub(crate) struct WsIo<S> {
+    inner: WebSocketStream<S>,
+    read_buf: Vec<u8>,
+}
+
+impl<S> WsIo<S> {
+    pub(crate) fn new(inner: WebSocketStream<S>) -> WsIo<S> {
+        WsIo {
+            inner,
+            read_buf: Vec::new(),
+        }
+    }
+}
+
+impl<S> AsyncRead for WsIo<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        if !self.read_buf.is_empty() {
+            let n = self.read_buf.len().min(buf.remaining());
+            buf.put_slice(&self.read_buf[..n]);
+            self.read_buf.drain(..n);
+            return Poll::Ready(Ok(()));
+        }
+
+        loop {
+            return match ready!(self.inner.poll_next_unpin(cx)) {
+                Some(Ok(Message::Binary(data))) => {
+                    let n = data.len().min(buf.remaining());
+                    buf.put_slice(&data[..n]);
+                    if n < data.len() {
+                        self.read_buf.extend_from_slice(&data[n..]);
+                    }
+                    Poll::Ready(Ok(()))
+                }
+                // Ping/Pong control frames carry no MQTT bytes; skip them and poll again.
+                Some(Ok(Message::Ping(_))) | Some(Ok(Message::Pong(_))) => continue,
+                // A clean close reads as EOF, same as the stream simply ending - not an error.
+                Some(Ok(Message::Close(_))) => Poll::Ready(Ok(())),
+                Some(Ok(_)) => Poll::Ready(Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "expected binary MQTT-over-WebSocket frame",
+                ))),
+                Some(Err(e)) => {
+                    Poll::Ready(Err(std::io::Error::new(std::io::ErrorKind::Other, e)))
+                }
+                None => Poll::Ready(Ok(())),
+            };
+        }
+    }
+}
+
+impl<S> AsyncWrite for WsIo<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match ready!(self.inner.poll_ready_unpin(cx)) {
+            Ok(()) => {}
+            Err(e) => return Poll::Ready(Err(std::io::Error::new(std::io::ErrorKind::Other, e))),
+        }
+
+        match self.inner.start_send_unpin(Message::Binary(buf.to_vec())) {
+            Ok(()) => Poll::Ready(Ok(buf.len())),
+            Err(e) => Poll::Ready(Err(std::io::Error::new(std::io::ErrorKind::Other, e))),
+        }
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.inner
+            .poll_flush_unpin(cx)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.inner
+            .poll_close_unpin(cx)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
+    }
+}